@@ -0,0 +1,131 @@
+//! Async, interrupt-driven bxcan driver.
+//!
+//! Ports the bxcan TX/RX/SCE interrupts to embassy-style waker-based
+//! futures: each interrupt handler wakes a static `AtomicWaker`, and
+//! `transmit`/`receive` are built with `poll_fn` so the serial RX task and
+//! the CAN RX task can run concurrently instead of busy-spinning. Back
+//! pressure from a full TX mailbox suspends the caller rather than
+//! dropping the frame.
+
+use core::future::poll_fn;
+use core::marker::PhantomData;
+use core::task::Poll;
+
+use bxcan::Instance;
+use embassy_sync::waker::AtomicWaker;
+
+use crate::timestamp;
+
+static TX_WAKER: AtomicWaker = AtomicWaker::new();
+static RX_WAKER: AtomicWaker = AtomicWaker::new();
+static SCE_WAKER: AtomicWaker = AtomicWaker::new();
+
+/// A received frame paired with the timestamp it arrived at.
+///
+/// Mirrors embassy's `Envelope`. `ts` tracks [`crate::timestamp::now_ms`]
+/// at the moment of reception regardless of whether slcan `Z` mode is
+/// enabled; callers decide whether to surface it.
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    pub ts: u16,
+    pub frame: bxcan::Frame,
+}
+
+/// Wake the TX future whenever a mailbox empties.
+pub struct TxInterruptHandler<I> {
+    _instance: PhantomData<I>,
+}
+
+impl<I: Instance> TxInterruptHandler<I> {
+    pub fn on_interrupt() {
+        TX_WAKER.wake();
+    }
+}
+
+/// Wake the RX future whenever FIFO 0 receives a frame.
+pub struct Rx0InterruptHandler<I> {
+    _instance: PhantomData<I>,
+}
+
+impl<I: Instance> Rx0InterruptHandler<I> {
+    pub fn on_interrupt() {
+        RX_WAKER.wake();
+    }
+}
+
+/// Wake the RX future whenever FIFO 1 receives a frame.
+pub struct Rx1InterruptHandler<I> {
+    _instance: PhantomData<I>,
+}
+
+impl<I: Instance> Rx1InterruptHandler<I> {
+    pub fn on_interrupt() {
+        RX_WAKER.wake();
+    }
+}
+
+/// Wake anything waiting on bus status (see [`crate::error_status`]) on a
+/// status-change-error event.
+pub struct SceInterruptHandler<I> {
+    _instance: PhantomData<I>,
+}
+
+impl<I: Instance> SceInterruptHandler<I> {
+    pub fn on_interrupt() {
+        SCE_WAKER.wake();
+    }
+}
+
+/// Wraps a `bxcan::Can` with async `transmit`/`receive`, driven by the
+/// interrupt handlers above instead of polling the peripheral directly.
+pub struct CanDriver<I: Instance> {
+    can: bxcan::Can<I>,
+}
+
+impl<I: Instance> CanDriver<I> {
+    pub fn new(can: bxcan::Can<I>) -> Self {
+        CanDriver { can }
+    }
+
+    /// Enqueue `frame` for transmission, suspending the task instead of
+    /// dropping the frame if every TX mailbox is currently full.
+    ///
+    /// If all three mailboxes already hold a pending lower-priority frame,
+    /// `bxcan` evicts one of them to make room; that evicted frame is
+    /// re-submitted in a loop until it is queued too, so nothing is lost.
+    pub async fn transmit(&mut self, frame: &bxcan::Frame) {
+        let mut pending = frame.clone();
+        poll_fn(|cx| {
+            TX_WAKER.register(cx.waker());
+            loop {
+                match self.can.transmit(&pending) {
+                    Ok(None) => return Poll::Ready(()),
+                    Ok(Some(evicted)) => {
+                        pending = evicted;
+                        continue;
+                    }
+                    Err(nb::Error::WouldBlock) => return Poll::Pending,
+                    Err(nb::Error::Other(never)) => match never {},
+                }
+            }
+        })
+        .await
+    }
+
+    /// Wait for the next received frame, stamped with the timestamp it
+    /// arrived at.
+    pub async fn receive(&mut self) -> Envelope {
+        poll_fn(|cx| {
+            RX_WAKER.register(cx.waker());
+            match self.can.receive() {
+                Ok(frame) => Poll::Ready(Envelope {
+                    ts: timestamp::now_ms(),
+                    frame,
+                }),
+                Err(nb::Error::WouldBlock) => Poll::Pending,
+                Err(nb::Error::Other(_)) => Poll::Pending,
+            }
+        })
+        .await
+    }
+}
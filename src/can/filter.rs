@@ -0,0 +1,109 @@
+//! Hardware acceptance filtering driven by the slcan `M`/`m` commands.
+//!
+//! Classic slcan exposes the SJA1000's acceptance code/mask registers
+//! directly: `M<8 hex>` sets the acceptance code, `m<8 hex>` sets the
+//! acceptance mask, each a 32-bit word packing the CAN id (standard or
+//! extended), the RTR bit, and the frame-format bit the same way
+//! `bxcan::Id`/`embedded_hal::can::Id` do. This module translates that
+//! packed code/mask pair into one of bxcan's 14 filter bank entries.
+
+use bxcan::filter::Mask32;
+use bxcan::{ExtendedId, Id, StandardId};
+
+/// Set when the packed code/mask word describes an extended (29-bit) id.
+const EXTENDED_FLAG: u32 = 1 << 31;
+/// Set when the packed code/mask word constrains the RTR bit.
+const RTR_FLAG: u32 = 1 << 30;
+
+const STANDARD_ID_MASK: u32 = 0x7FF;
+const EXTENDED_ID_MASK: u32 = 0x1FFF_FFFF;
+
+/// The acceptance code/mask pair as programmed via `M`/`m`.
+///
+/// `mask` bits that are `0` are "don't care"; this matches both the SJA1000
+/// AMR convention and `bxcan::filter::Mask32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcceptanceFilter {
+    code: u32,
+    mask: u32,
+}
+
+impl AcceptanceFilter {
+    /// Accept every frame: an all-zero mask, matching a cleared AMR.
+    pub const fn accept_all() -> Self {
+        AcceptanceFilter { code: 0, mask: 0 }
+    }
+
+    /// Set the acceptance code from a parsed slcan `M<8 hex>` command.
+    pub fn set_code(&mut self, code: u32) {
+        self.code = code;
+    }
+
+    /// Set the acceptance mask from a parsed slcan `m<8 hex>` command.
+    pub fn set_mask(&mut self, mask: u32) {
+        self.mask = mask;
+    }
+
+    /// Reset to "accept all", as if the mask had been cleared.
+    pub fn clear(&mut self) {
+        *self = Self::accept_all();
+    }
+
+    /// The RTR bit requested by the acceptance code, if the mask constrains
+    /// it. `bxcan::filter::Mask32` only filters on id, so the CAN RX path
+    /// must check this itself against a matched frame.
+    pub fn rtr(&self) -> Option<bool> {
+        (self.mask & RTR_FLAG != 0).then(|| self.code & RTR_FLAG != 0)
+    }
+
+    /// Translate the packed code/mask pair into a bxcan `Mask32` filter
+    /// bank entry.
+    pub fn to_bxcan_filter(&self) -> Mask32 {
+        if self.mask == 0 {
+            return Mask32::accept_all();
+        }
+
+        let extended = self.code & EXTENDED_FLAG != 0;
+        if extended {
+            // both values are masked to 29 bits, so these never fail
+            let id = ExtendedId::new(self.code & EXTENDED_ID_MASK).unwrap();
+            let mask = ExtendedId::new(self.mask & EXTENDED_ID_MASK).unwrap();
+            Mask32::frames_with_ext_id(id, mask)
+        } else {
+            let id = StandardId::new((self.code & STANDARD_ID_MASK) as u16)
+                .unwrap_or_else(|| StandardId::new(0).unwrap());
+            let mask = StandardId::new((self.mask & STANDARD_ID_MASK) as u16)
+                .unwrap_or_else(|| StandardId::new(0).unwrap());
+            Mask32::frames_with_std_id(id, mask)
+        }
+    }
+}
+
+impl Default for AcceptanceFilter {
+    fn default() -> Self {
+        Self::accept_all()
+    }
+}
+
+/// Program bank 0 with `filter`, replacing whatever was configured before.
+///
+/// Called during the slcan `O` (open channel) transition, after `M`/`m` have
+/// been parsed into `filter`.
+pub fn program<I: bxcan::FilterOwner>(
+    filters: &mut bxcan::filter::MasterFilters<'_, I>,
+    filter: &AcceptanceFilter,
+) {
+    filters.clear();
+    filters.enable_bank(0, bxcan::Fifo::Fifo0, filter.to_bxcan_filter());
+}
+
+/// Convert a [`bxcan::Id`] to the code-word id bits, for building a manual
+/// `AcceptanceFilter` outside of the raw `M` command path.
+///
+/// The filter-word counterpart of [`crate::bxcan_to_canserial_id`].
+pub fn id_to_code_bits(id: &Id) -> u32 {
+    match id {
+        Id::Standard(stdid) => stdid.as_raw() as u32,
+        Id::Extended(extid) => extid.as_raw() | EXTENDED_FLAG,
+    }
+}
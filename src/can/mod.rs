@@ -0,0 +1,8 @@
+//! bxcan peripheral configuration helpers.
+//!
+//! Everything in [`crate`] that is pure frame <-> ascii conversion lives at
+//! the crate root; this module holds the pieces that talk to the bxcan
+//! filter banks and the interrupt-driven driver built on top of them.
+
+pub mod driver;
+pub mod filter;
@@ -0,0 +1,133 @@
+//! Bus error/status tracking for the slcan `F` command.
+//!
+//! Modeled on bxcan's SCE (status change error) interrupt and its
+//! `Lec`/last-error-code decoding: each SCE event updates a bit-field of
+//! controller health flags, which is reported to the host either on demand
+//! (`F` command) or proactively when the link degrades (bus-off or
+//! error-passive).
+
+use core::fmt::Write;
+
+/// Status flags reported by the slcan `F<2 hex>\r` command.
+///
+/// Bit layout matches the lawicel/canusb `CANSTATUS` byte.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StatusFlags(u8);
+
+impl StatusFlags {
+    pub const RX_FIFO_FULL: u8 = 1 << 0;
+    pub const TX_FIFO_FULL: u8 = 1 << 1;
+    pub const ERROR_WARNING: u8 = 1 << 2;
+    pub const DATA_OVERRUN: u8 = 1 << 3;
+    pub const ERROR_PASSIVE: u8 = 1 << 5;
+    pub const ARBITRATION_LOST: u8 = 1 << 6;
+    pub const BUS_OFF: u8 = 1 << 7;
+
+    pub const fn empty() -> Self {
+        StatusFlags(0)
+    }
+
+    pub fn set(&mut self, bit: u8, value: bool) {
+        if value {
+            self.0 |= bit;
+        } else {
+            self.0 &= !bit;
+        }
+    }
+
+    pub fn contains(&self, bit: u8) -> bool {
+        self.0 & bit != 0
+    }
+
+    /// The raw status byte, as sent in the `F` command response.
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+}
+
+/// Tracks bus health across SCE interrupts and produces the `F` status byte.
+#[derive(Debug, Default)]
+pub struct ErrorTracker {
+    flags: StatusFlags,
+    was_bus_off: bool,
+    was_error_passive: bool,
+}
+
+impl ErrorTracker {
+    pub const fn new() -> Self {
+        ErrorTracker {
+            flags: StatusFlags::empty(),
+            was_bus_off: false,
+            was_error_passive: false,
+        }
+    }
+
+    /// Update tracked state from a bxcan SCE read: the last-error-code,
+    /// receive/transmit error counters, and the RX FIFO overrun flag.
+    ///
+    /// Returns `true` if the bus just transitioned into error-passive,
+    /// meaning the caller should proactively emit the `F` status byte
+    /// rather than waiting to be polled. Bus-off transitions are reported
+    /// through [`Self::set_bus_off`] instead, since bus-off is driven by
+    /// the `ESR` `BOFF` bit rather than by these error counters.
+    pub fn on_sce_event(
+        &mut self,
+        lec: Option<bxcan::LastErrorCode>,
+        receive_error_count: u8,
+        transmit_error_count: u8,
+        rx_fifo_overrun: bool,
+    ) -> bool {
+        let error_passive = receive_error_count >= 128 || transmit_error_count >= 128;
+        let error_warning = receive_error_count >= 96 || transmit_error_count >= 96;
+
+        self.flags.set(StatusFlags::ERROR_WARNING, error_warning);
+        self.flags.set(StatusFlags::ERROR_PASSIVE, error_passive);
+        self.flags.set(StatusFlags::DATA_OVERRUN, rx_fifo_overrun);
+        self.flags.set(
+            StatusFlags::ARBITRATION_LOST,
+            matches!(lec, Some(bxcan::LastErrorCode::ArbitrationLost)),
+        );
+
+        let newly_error_passive = error_passive && !self.was_error_passive;
+        self.was_error_passive = error_passive;
+        newly_error_passive
+    }
+
+    /// Record that the bus has gone bus-off (from the `BOFF` bit in `ESR`).
+    pub fn set_bus_off(&mut self, bus_off: bool) -> bool {
+        self.flags.set(StatusFlags::BUS_OFF, bus_off);
+        let newly_off = bus_off && !self.was_bus_off;
+        self.was_bus_off = bus_off;
+        newly_off
+    }
+
+    pub fn set_rx_fifo_full(&mut self, full: bool) {
+        self.flags.set(StatusFlags::RX_FIFO_FULL, full);
+    }
+
+    pub fn set_tx_fifo_full(&mut self, full: bool) {
+        self.flags.set(StatusFlags::TX_FIFO_FULL, full);
+    }
+
+    /// Current status flags, as reported by the `F` command.
+    pub fn flags(&self) -> StatusFlags {
+        self.flags
+    }
+
+    /// Whether the link currently has an active fault (bus-off or
+    /// error-passive), as opposed to a merely transient bit (error-warning,
+    /// arbitration-lost, data-overrun) that `on_sce_event` latches until
+    /// the next SCE read. Only an active fault should gate a proactive
+    /// error notification; transient bits must never cause a valid,
+    /// already-received frame to be dropped or replaced.
+    pub fn has_active_fault(&self) -> bool {
+        self.was_bus_off || self.was_error_passive
+    }
+}
+
+/// Format the `F` status command response: `F<2 hex>\r`.
+pub fn format_status(flags: StatusFlags) -> heapless::Vec<u8, 4> {
+    let mut buffer: heapless::Vec<u8, 4> = heapless::Vec::new();
+    core::write!(&mut buffer, "F{:02X}\r", flags.bits()).ok();
+    buffer
+}
@@ -0,0 +1,75 @@
+//! Distinct CAN frame kinds carried between the CAN and serial sides.
+//!
+//! Borrows socketcan's split of `CanDataFrame`/`CanRemoteFrame`/
+//! `CanErrorFrame` behind a `CanAnyFrame` enum, instead of collapsing
+//! everything (including controller error conditions) into one
+//! `CanserialFrame` and silently dropping what doesn't fit.
+
+use heapless::Vec;
+use slcan_parser::CanserialFrame;
+
+use crate::error_status::{ErrorTracker, StatusFlags};
+
+/// A controller error condition, captured from the SCE handler (see
+/// [`crate::error_status`]) rather than from a received `bxcan::Frame`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanErrorFrame {
+    pub flags: StatusFlags,
+}
+
+impl CanErrorFrame {
+    /// Snapshot the current status out of `tracker` as a `CanErrorFrame`.
+    pub fn from_tracker(tracker: &ErrorTracker) -> Self {
+        CanErrorFrame {
+            flags: tracker.flags(),
+        }
+    }
+}
+
+/// Any of the frame kinds the bridge carries: a data frame, a remote (RTR)
+/// frame, or a controller error condition.
+pub enum AnyFrame {
+    Data(CanserialFrame),
+    Remote(CanserialFrame),
+    Error(CanErrorFrame),
+}
+
+impl AnyFrame {
+    /// The underlying `CanserialFrame`, consuming `self`, if this is a data
+    /// or remote frame.
+    pub fn into_canserial(self) -> Option<CanserialFrame> {
+        match self {
+            AnyFrame::Data(f) | AnyFrame::Remote(f) => Some(f),
+            AnyFrame::Error(_) => None,
+        }
+    }
+}
+
+/// Why a `bxcan::Frame` couldn't be represented as an [`AnyFrame`].
+///
+/// This only ever covers a bad id or data shape. A controller error
+/// condition is never substituted for a frame conversion result — it's
+/// reported out-of-band, via [`crate::error_notification_to_vec`], so a
+/// real, already-dequeued frame is never silently thrown away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The id or data didn't fit the target representation.
+    NotRepresentable,
+}
+
+/// Format any frame kind onto the serial link: the existing
+/// `<canserial ascii>\r` for data/remote frames, or the slcan `F<2 hex>\r`
+/// status notification for a controller error condition.
+pub fn any_frame_to_vec(frame: &AnyFrame, timestamp: Option<u16>) -> Vec<u8, 32> {
+    use core::fmt::Write;
+
+    let mut buffer: Vec<u8, 32> = Vec::new();
+    match frame {
+        AnyFrame::Data(f) | AnyFrame::Remote(f) => match timestamp {
+            Some(ts) => core::write!(&mut buffer, "{}{:04X}\r", f, ts).ok(),
+            None => core::write!(&mut buffer, "{}\r", f).ok(),
+        },
+        AnyFrame::Error(err) => core::write!(&mut buffer, "F{:02X}\r", err.flags.bits()).ok(),
+    };
+    buffer
+}
@@ -19,6 +19,11 @@ use defmt_rtt as _; // global logger
 use panic_probe as _;
 
 pub mod can;
+pub mod error_status;
+pub mod frame;
+pub mod timestamp;
+
+use frame::{AnyFrame, CanErrorFrame, ConversionError};
 
 // same panicking *behavior* as `panic-probe` but doesn't print a panic message
 // this prevents the panic message being printed *twice* when `defmt::panic` is invoked
@@ -48,12 +53,36 @@ unsafe fn HardFault(_frame: &cortex_m_rt::ExceptionFrame) -> ! {
 }
 
 /// Convert `CanFrame` to `Vec` containing ascii string generated by a `CanserialFrame`
-pub fn bxcan_to_vec(bxcan_frame: &bxcan::Frame) -> Option<Vec<u8, 32>> {
-    let frame = bxcan_to_canserial(bxcan_frame)?;
-    // longest canserial ascii string for an extended id frame is 26 bytes
-    let mut buffer: Vec<u8, 32> = Vec::new();
-    core::write!(&mut buffer, "{}\r", frame).ok();
-    Some(buffer)
+///
+/// `timestamp`, when `Some`, is a millisecond counter in `0..0xEA60` (see
+/// [`crate::timestamp`]) appended as 4 hex digits before the trailing `\r`,
+/// per the slcan `Z1` timestamp mode. Returns `None` if `bxcan_frame` isn't
+/// representable on the slcan wire; see [`bxcan_to_any_frame`]. Controller
+/// error conditions are never substituted in here — see
+/// [`error_notification_to_vec`] for the out-of-band path that reports
+/// them instead.
+pub fn bxcan_to_vec(bxcan_frame: &bxcan::Frame, timestamp: Option<u16>) -> Option<Vec<u8, 32>> {
+    let frame = bxcan_to_any_frame(bxcan_frame).ok()?;
+    // longest canserial ascii string for an extended id frame is 26 bytes,
+    // plus 4 hex digits for the timestamp
+    Some(frame::any_frame_to_vec(&frame, timestamp))
+}
+
+/// Build the out-of-band slcan notification for a controller fault
+/// tracked by `tracker`, if one is currently active.
+///
+/// Call this when [`error_status::ErrorTracker::on_sce_event`] or
+/// [`error_status::ErrorTracker::set_bus_off`] reports a fresh
+/// transition, not as part of converting a received frame: a fault must
+/// never replace or discard an already-dequeued data/remote frame, so
+/// this is entirely independent of [`bxcan_to_vec`]. Returns `None` while
+/// only transient bits (error-warning, arbitration-lost, data-overrun)
+/// are set, since those alone don't warrant interrupting the frame
+/// stream with a status byte.
+pub fn error_notification_to_vec(tracker: &error_status::ErrorTracker) -> Option<Vec<u8, 32>> {
+    tracker.has_active_fault().then(|| {
+        frame::any_frame_to_vec(&AnyFrame::Error(CanErrorFrame::from_tracker(tracker)), None)
+    })
 }
 
 /// Convert `bxcan::Id` to `Id`
@@ -73,20 +102,33 @@ pub fn bxcan_to_canserial_id(id: &bxcan::Id) -> Option<Id> {
 }
 
 /// Convert `bxcan::Frame` to `CanserialFrame` for use with serial port
+///
+/// Discards the data/remote distinction captured by [`bxcan_to_any_frame`];
+/// kept for callers that only care about the frame payload.
 pub fn bxcan_to_canserial(bcanframe: &bxcan::Frame) -> Option<CanserialFrame> {
-    match bcanframe.is_remote_frame() {
-        true => CanserialFrame::new_remote(
-            bxcan_to_canserial_id(&bcanframe.id())?,
-            bcanframe.dlc() as usize,
-        ),
-        false => match bcanframe.data() {
-            Some(d) => CanserialFrame::new_frame(
-                bxcan_to_canserial_id(&bcanframe.id())?,
-                d.get(0..d.len())?,
-            ),
-            // possible to have an empty data frame
-            None => CanserialFrame::new_frame(bxcan_to_canserial_id(&bcanframe.id())?, &[]),
-        },
+    bxcan_to_any_frame(bcanframe)
+        .ok()
+        .and_then(AnyFrame::into_canserial)
+}
+
+/// Convert `bxcan::Frame` to an [`AnyFrame`], distinguishing data frames
+/// from remote frames rather than collapsing both into one shape.
+///
+/// Returns `Err(ConversionError::NotRepresentable)` if the id or data
+/// doesn't fit the slcan wire format. Controller error conditions aren't
+/// produced here, since they come from the SCE handler rather than a
+/// received frame; see [`error_notification_to_vec`] for those.
+pub fn bxcan_to_any_frame(bcanframe: &bxcan::Frame) -> Result<AnyFrame, ConversionError> {
+    let id = bxcan_to_canserial_id(&bcanframe.id()).ok_or(ConversionError::NotRepresentable)?;
+    if bcanframe.is_remote_frame() {
+        CanserialFrame::new_remote(id, bcanframe.dlc() as usize)
+            .map(AnyFrame::Remote)
+            .ok_or(ConversionError::NotRepresentable)
+    } else {
+        let data = bcanframe.data().map(|d| &d[..]).unwrap_or(&[]);
+        CanserialFrame::new_frame(id, data)
+            .map(AnyFrame::Data)
+            .ok_or(ConversionError::NotRepresentable)
     }
 }
 
@@ -128,6 +170,130 @@ pub fn canserial_to_bxcan(slcan: &CanserialFrame) -> Option<bxcan::Frame> {
 mod unit_tests {
     use super::*;
 
+    #[test]
+    fn test_parse_z_command() {
+        assert_eq!(timestamp::parse_z_command(b'0'), Some(false));
+        assert_eq!(timestamp::parse_z_command(b'1'), Some(true));
+        assert_eq!(timestamp::parse_z_command(b'x'), None);
+    }
+
+    #[test]
+    fn test_on_sce_event_error_passive_transition() {
+        let mut tracker = error_status::ErrorTracker::new();
+
+        // below the error-passive threshold: no transition yet
+        assert!(!tracker.on_sce_event(None, 64, 0, false));
+        assert!(!tracker
+            .flags()
+            .contains(error_status::StatusFlags::ERROR_PASSIVE));
+
+        // crossing the threshold reports a fresh transition once
+        assert!(tracker.on_sce_event(None, 128, 0, false));
+        assert!(tracker
+            .flags()
+            .contains(error_status::StatusFlags::ERROR_PASSIVE));
+        assert!(!tracker.on_sce_event(None, 128, 0, false));
+
+        // recovering back below the threshold clears the flag
+        assert!(!tracker.on_sce_event(None, 0, 0, false));
+        assert!(!tracker
+            .flags()
+            .contains(error_status::StatusFlags::ERROR_PASSIVE));
+    }
+
+    #[test]
+    fn test_on_sce_event_does_not_drive_bus_off() {
+        let mut tracker = error_status::ErrorTracker::new();
+        tracker.set_bus_off(true);
+
+        // a quiescent SCE read (both counters at zero) must not clear or
+        // re-latch BUS_OFF; only `set_bus_off` may change it
+        tracker.on_sce_event(None, 0, 0, false);
+        assert!(tracker.flags().contains(error_status::StatusFlags::BUS_OFF));
+    }
+
+    #[test]
+    fn test_error_notification_ignores_stale_transient_flags() {
+        let mut tracker = error_status::ErrorTracker::new();
+        // error-warning (>=96) is a transient bit, not an active fault
+        tracker.on_sce_event(None, 100, 0, false);
+        assert!(tracker
+            .flags()
+            .contains(error_status::StatusFlags::ERROR_WARNING));
+        assert!(!tracker.has_active_fault());
+        assert!(error_notification_to_vec(&tracker).is_none());
+
+        tracker.set_bus_off(true);
+        assert!(tracker.has_active_fault());
+        let buffer = error_notification_to_vec(&tracker).unwrap();
+        assert!(buffer.starts_with(b"F"));
+    }
+
+    #[test]
+    fn test_bxcan_to_vec_never_drops_a_valid_frame_for_stale_flags() {
+        let mut tracker = error_status::ErrorTracker::new();
+        // a stale transient flag, latched by on_sce_event and never an
+        // active fault, must never cause a valid frame's conversion to be
+        // thrown away and replaced by a status byte
+        tracker.on_sce_event(None, 100, 0, false);
+        assert!(!tracker.has_active_fault());
+
+        let id = bxcan::Id::Standard(bxcan::StandardId::new(0x1).unwrap());
+        let bframe = bxcan::Frame::new_data(id, bxcan::Data::new(&[0xAA]).unwrap());
+        let buffer = bxcan_to_vec(&bframe, None).unwrap();
+        assert!(!buffer.starts_with(b"F"));
+    }
+
+    #[test]
+    fn test_to_bxcan_filter_std_and_extended() {
+        use can::filter::AcceptanceFilter;
+
+        let mut filter = AcceptanceFilter::accept_all();
+        assert_eq!(filter.rtr(), None);
+        let _ = filter.to_bxcan_filter();
+
+        // standard id/mask, RTR bit constrained to "data frames only"
+        filter.set_code(0x123);
+        filter.set_mask(0x7FF | (1 << 30));
+        assert_eq!(filter.rtr(), Some(false));
+        let _ = filter.to_bxcan_filter();
+
+        // extended id/mask
+        filter.set_code(0x1ABCDEF | (1 << 31));
+        filter.set_mask(0x1FFF_FFFF | (1 << 31));
+        assert_eq!(filter.rtr(), None);
+        let _ = filter.to_bxcan_filter();
+
+        filter.clear();
+        assert_eq!(filter, AcceptanceFilter::accept_all());
+    }
+
+    #[test]
+    fn test_any_frame_to_vec_data_with_timestamp() {
+        let id = Id::Standard(StandardId::new(0x1).unwrap());
+        let cframe = CanserialFrame::new_frame(id, &[0xAA]).unwrap();
+        let buffer = frame::any_frame_to_vec(&AnyFrame::Data(cframe), Some(0x1234));
+        assert!(buffer.ends_with(b"1234\r"));
+    }
+
+    #[test]
+    fn test_any_frame_to_vec_data_without_timestamp() {
+        let id = Id::Standard(StandardId::new(0x1).unwrap());
+        let cframe = CanserialFrame::new_frame(id, &[0xAA]).unwrap();
+        let buffer = frame::any_frame_to_vec(&AnyFrame::Data(cframe), None);
+        assert!(buffer.ends_with(b"\r"));
+        assert!(!buffer[..buffer.len() - 1].ends_with(b"\r"));
+    }
+
+    #[test]
+    fn test_any_frame_to_vec_error() {
+        let mut tracker = error_status::ErrorTracker::new();
+        tracker.set_bus_off(true);
+        let err = CanErrorFrame::from_tracker(&tracker);
+        let buffer = frame::any_frame_to_vec(&AnyFrame::Error(err), None);
+        assert_eq!(&buffer[..], b"F80\r");
+    }
+
     #[test]
     fn test_canserial_to_bxcan() {
         let id = Id::Standard(StandardId::new(0x1).unwrap());
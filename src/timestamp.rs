@@ -0,0 +1,61 @@
+//! Millisecond receive timestamps for the slcan `Z` command.
+//!
+//! slcan hosts that enable timestamping (`Z1`) expect every received frame to
+//! be suffixed with a free-running 4 hex digit millisecond counter that wraps
+//! at `0xEA60` (60000 ms), matching the canusb/lawicel convention. The
+//! counter itself is driven by a periodic tick (SysTick or a timer
+//! peripheral) calling [`on_tick`]; the slcan command handler toggles
+//! [`set_enabled`] when it parses a `Z` command.
+
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Timestamps wrap at 60000 ms (0xEA60), per the slcan `Z` command spec.
+pub const TIMESTAMP_MODULUS_MS: u32 = 0xEA60;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static TICKS_MS: AtomicU32 = AtomicU32::new(0);
+
+/// Advance the free-running millisecond counter by one tick.
+///
+/// Call this once per millisecond from the SysTick (or equivalent timer)
+/// interrupt handler.
+pub fn on_tick() {
+    TICKS_MS
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |t| {
+            Some((t + 1) % TIMESTAMP_MODULUS_MS)
+        })
+        .ok();
+}
+
+/// Current free-running timestamp, in the `0..TIMESTAMP_MODULUS_MS` range.
+pub fn now_ms() -> u16 {
+    TICKS_MS.load(Ordering::Relaxed) as u16
+}
+
+/// Enable or disable timestamp reporting, per the `Z1`/`Z0` slcan command.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether timestamp reporting is currently enabled.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Timestamp to attach to a received frame right now, if the mode is on.
+///
+/// Convenience wrapper around [`enabled`] and [`now_ms`] for callers (such as
+/// the CAN RX path) that just want "the stamp to use, or none".
+pub fn sample() -> Option<u16> {
+    enabled().then(now_ms)
+}
+
+/// Parse a slcan `Z` command payload (`b'0'` or `b'1'` following the `Z`
+/// character) into the enable flag it requests.
+pub fn parse_z_command(arg: u8) -> Option<bool> {
+    match arg {
+        b'0' => Some(false),
+        b'1' => Some(true),
+        _ => None,
+    }
+}